@@ -3,11 +3,13 @@
 //!
 //! *Signed: kryon.kas*
 
+mod paper_wallet;
 mod vanity;
 
 use clap::Parser;
 use rayon::prelude::*;
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 use crate::vanity::generate_random_mnemonic;
@@ -18,15 +20,16 @@ use crate::vanity::generate_random_mnemonic;
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    /// Prefix to search for (e.g., "test")
-    /// NB: Matching starts from the 3rd character of the Bech32 payload.
-    #[arg(short, long)]
-    prefix: Option<String>,
+    /// Grind target of the form "PATTERN:COUNT" matched against the start of
+    /// the payload, e.g. "abc:2" stops once two distinct "abc..." addresses
+    /// have been found. May be repeated to grind several prefixes at once.
+    #[arg(long = "starts-with", value_name = "PATTERN:COUNT")]
+    starts_with: Vec<String>,
 
-    /// Suffix to search for (e.g., "2025")
-    /// NB: Long suffixes are computationally expensive due to the checksum.
-    #[arg(short, long)]
-    suffix: Option<String>,
+    /// Grind target of the form "PATTERN:COUNT" matched against the end of
+    /// the payload. May be repeated to grind several suffixes at once.
+    #[arg(long = "ends-with", value_name = "PATTERN:COUNT")]
+    ends_with: Vec<String>,
 
     /// Number of threads to use.
     /// Défaut: All logical cores.
@@ -41,17 +44,258 @@ struct Args {
     #[arg(short, long, default_value_t = 24)]
     words: usize,
 
-    /// Address scan limit per mnemonic.
+    /// Address index scan limit per account, per mnemonic.
     /// Increases speed by checking multiple indices (0..N) per seed.
     #[arg(long, default_value_t = 1)]
     scan_limit: u32,
+
+    /// Number of consecutive accounts to scan per mnemonic, starting at
+    /// `--account`. Combined with `--scan-limit`, one generated seed yields
+    /// `accounts * scan-limit` candidate addresses. Incompatible with `--path`.
+    #[arg(long, default_value_t = 1, conflicts_with = "path")]
+    accounts: u32,
+
+    /// BIP-44 account index (the hardened `account'` component). Together
+    /// with `--accounts`, every scanned offset must stay below the hardened
+    /// index boundary (2^31); checked at startup.
+    #[arg(long, default_value_t = 0, conflicts_with = "path")]
+    account: u32,
+
+    /// BIP-44 change chain: 0 (external/receive) or 1 (internal/change).
+    #[arg(long, default_value_t = 0, conflicts_with = "path", value_parser = clap::value_parser!(u32).range(0..=1))]
+    change: u32,
+
+    /// Escape hatch: a full BIP-32 derivation path below the master key
+    /// (e.g. "m/44'/111111'/0'/0"), used as-is instead of `--account`/
+    /// `--change`. Disables multi-account scanning.
+    #[arg(long, conflicts_with_all = ["account", "change"])]
+    path: Option<String>,
+
+    /// Path to append each match's mnemonic, address and derivation path to,
+    /// one plaintext JSON object per line (JSON Lines). Use "-" for stdout.
+    /// A pre-existing file is refused at startup unless `--force` is given;
+    /// matches found during the same run are always appended, never
+    /// overwritten. This file is NOT encrypted — protect it like the
+    /// mnemonic itself.
+    #[arg(long, default_value = "vanity-result.json", conflicts_with = "no_outfile")]
+    outfile: String,
+
+    /// Disable writing matches to a file; print to the terminal only.
+    #[arg(long, default_value_t = false)]
+    no_outfile: bool,
+
+    /// Allow `--outfile` to overwrite an existing file.
+    #[arg(long, default_value_t = false)]
+    force: bool,
+
+    /// BIP-39 wordlist language. Only "english" is available: the other
+    /// wordlists are gated behind bip39's per-language cargo features (or
+    /// its "all-languages" feature), which this project's manifest doesn't
+    /// enable yet.
+    #[arg(long, value_enum, default_value_t = CliLanguage::English)]
+    language: CliLanguage,
+
+    /// Prompt for an optional BIP-39 passphrase (the "25th word") applied to
+    /// every generated mnemonic. Never accepted as a plain CLI argument, so
+    /// it can't leak into shell history.
+    #[arg(long, default_value_t = false)]
+    passphrase: bool,
+
+    /// Network to derive and encode addresses for.
+    #[arg(long, value_enum, default_value_t = CliNetwork::Mainnet)]
+    network: CliNetwork,
+
+    /// Also render each match as a printable, offline paper-wallet PDF
+    /// (address QR code on one page, mnemonic QR code on the other) at the
+    /// given path. A run that finds more than one match gets `-1`, `-2`, ...
+    /// inserted before the extension of each PDF after the first, so later
+    /// matches don't overwrite earlier ones.
+    #[arg(long, value_name = "PATH")]
+    paper_wallet: Option<String>,
+}
+
+/// Networks exposed on the CLI, mapped onto `kaspa_addresses::Prefix`.
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum CliNetwork {
+    Mainnet,
+    Testnet,
+    Simnet,
+    Devnet,
+}
+
+impl From<CliNetwork> for kaspa_addresses::Prefix {
+    fn from(network: CliNetwork) -> Self {
+        match network {
+            CliNetwork::Mainnet => kaspa_addresses::Prefix::Mainnet,
+            CliNetwork::Testnet => kaspa_addresses::Prefix::Testnet,
+            CliNetwork::Simnet => kaspa_addresses::Prefix::Simnet,
+            CliNetwork::Devnet => kaspa_addresses::Prefix::Devnet,
+        }
+    }
+}
+
+/// BIP-39 wordlist languages exposed on the CLI, mapped onto `bip39::Language`.
+///
+/// `bip39::Language` has a variant per wordlist, but every one other than
+/// `English` is compiled in only when its own cargo feature (or
+/// `all-languages`) is enabled; this project doesn't enable them, so only
+/// `English` is exposed here. Add the matching `CliLanguage` variant (and
+/// arm below) alongside turning on the relevant bip39 feature(s), not before.
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum CliLanguage {
+    English,
+}
+
+impl From<CliLanguage> for bip39::Language {
+    fn from(language: CliLanguage) -> Self {
+        match language {
+            CliLanguage::English => bip39::Language::English,
+        }
+    }
+}
+
+/// Prompts for the BIP-39 passphrase without echoing it to the terminal, so
+/// it never ends up in shell history or a screen-recording. One prompt
+/// covers the whole run: the same passphrase is reused for every generated
+/// mnemonic, since asking again per-candidate would make grinding unusable.
+fn acquire_passphrase(prompt_for_it: bool) -> String {
+    if !prompt_for_it {
+        return String::new();
+    }
+    rpassword::prompt_password("BIP-39 passphrase (25th word, empty for none): ")
+        .expect("Failed to read passphrase")
+}
+
+/// What gets written out for a single match.
+#[derive(serde::Serialize)]
+struct WalletExport {
+    mnemonic: String,
+    address: String,
+    derivation_path: String,
+}
+
+/// Refuses to run against a pre-existing `path` unless `force` is set. A
+/// vanity hunt can take hours, so this check happens up front: it would be
+/// worse to discover only after a long run that the mnemonic it found can't
+/// be saved. Matches found during the run itself are appended, never
+/// overwritten, so this check only needs to happen once.
+fn check_for_overwrite(path: &str, force: bool) {
+    if path == "-" || force {
+        return;
+    }
+    if std::path::Path::new(path).exists() {
+        eprintln!("Error: Refusing to overwrite existing file '{path}' (use --force)");
+        std::process::exit(1);
+    }
+}
+
+/// An outfile opened once up front and shared across grind threads, so
+/// concurrent matches append without interleaving each other's bytes.
+///
+/// `writeln!` on a freshly-`open`ed handle lowers to two syscalls (the JSON
+/// body, then the newline); with several rayon threads landing in the same
+/// `--outfile` around the same time, their writes can interleave and corrupt
+/// the JSON Lines file. Opening the handle once and serializing each append
+/// behind a `Mutex`, writing the pre-formatted `"{json}\n"` in a single
+/// `write_all`, keeps every line intact no matter how many threads match at
+/// once.
+enum Outfile {
+    Stdout,
+    File(std::sync::Mutex<std::io::BufWriter<std::fs::File>>),
+}
+
+impl Outfile {
+    /// Opens `path` for appending ("-" is kept as a stdout sentinel, not a file).
+    fn open(path: &str) -> Outfile {
+        if path == "-" {
+            return Outfile::Stdout;
+        }
+        let file = std::fs::OpenOptions::new().create(true).append(true).open(path).unwrap_or_else(|e| {
+            eprintln!("Error: Failed to open outfile '{path}': {e}");
+            std::process::exit(1);
+        });
+        Outfile::File(std::sync::Mutex::new(std::io::BufWriter::new(file)))
+    }
+
+    /// Appends a match's wallet export as one compact JSON object per line,
+    /// so a run that finds several matches accumulates all of them instead
+    /// of each one clobbering the last.
+    fn write_result(&self, path: &str, export: &WalletExport) {
+        let json = serde_json::to_string(export).expect("Failed to serialize wallet export");
+        match self {
+            Outfile::Stdout => println!("{json}"),
+            Outfile::File(writer) => {
+                use std::io::Write;
+                let mut writer = writer.lock().unwrap();
+                let result = writer.write_all(format!("{json}\n").as_bytes()).and_then(|()| writer.flush());
+                if let Err(e) = result {
+                    eprintln!("Error: Failed to append to outfile '{path}': {e}");
+                    std::process::exit(1);
+                }
+                println!("Appended wallet export to '{path}'");
+            }
+        }
+    }
+}
+
+/// A single grind target: match on `starts`/`ends` (either may be empty),
+/// counting down from the requested number of matches to zero.
+struct GrindMatch {
+    starts: String,
+    ends: String,
+    count: AtomicU64,
+}
+
+impl GrindMatch {
+    /// Parses a "PATTERN:COUNT" CLI value into a prefix-only or suffix-only target.
+    fn parse(spec: &str, is_prefix: bool, case_sensitive: bool, name: &str) -> GrindMatch {
+        let Some((pattern, count)) = spec.rsplit_once(':') else {
+            eprintln!("Error: Expected \"PATTERN:COUNT\" for {name} '{spec}'");
+            std::process::exit(1);
+        };
+        let Ok(count) = count.parse::<u64>() else {
+            eprintln!("Error: Invalid count '{count}' for {name} '{spec}'");
+            std::process::exit(1);
+        };
+        if count == 0 {
+            eprintln!("Error: Count must be at least 1 for {name} '{spec}'");
+            std::process::exit(1);
+        }
+
+        let pattern = if case_sensitive { pattern.to_string() } else { pattern.to_lowercase() };
+        validate_pattern(&pattern, name);
+
+        if is_prefix {
+            GrindMatch { starts: pattern, ends: String::new(), count: AtomicU64::new(count) }
+        } else {
+            GrindMatch { starts: String::new(), ends: pattern, count: AtomicU64::new(count) }
+        }
+    }
+
+    /// Whether `haystack` matches this target's prefix/suffix constraints.
+    /// `haystack` must already be case-normalized to match `self.starts`/`self.ends`
+    /// (done once per candidate address, not per target, since this runs in the hot loop).
+    fn matches(&self, haystack: &str) -> bool {
+        (self.starts.is_empty() || haystack.starts_with(&self.starts))
+            && (self.ends.is_empty() || haystack.ends_with(&self.ends))
+    }
+
+    /// Atomically decrements the remaining count (saturating at 0), returning
+    /// `true` if this call is the one that consumed a remaining match.
+    fn claim(&self) -> bool {
+        self.count
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |c| {
+                if c == 0 { None } else { Some(c - 1) }
+            })
+            .is_ok()
+    }
 }
 
 // --- Utilitaires ---
 
 /// Validates a search pattern (prefix or suffix) for invalid Bech32 characters.
-fn validate_pattern(pattern: &str, name: &str, invalid_chars: &[char]) {
-    if let Some(invalid_char) = pattern.chars().find(|c| invalid_chars.contains(c)) {
+fn validate_pattern(pattern: &str, name: &str) {
+    if let Some(invalid_char) = crate::vanity::first_invalid_bech32_char(pattern) {
         eprintln!("Error: Invalid character '{invalid_char}' in {name} '{pattern}'");
         eprintln!();
         eprintln!("Bech32 encoding excludes the following characters to avoid confusion:");
@@ -65,19 +309,29 @@ fn validate_pattern(pattern: &str, name: &str, invalid_chars: &[char]) {
     }
 }
 
+/// Inserts `-{n}` before `base`'s extension (or appends it, if there is
+/// none), giving each paper wallet from a multi-match run its own filename
+/// instead of every match after the first overwriting the one before it.
+fn indexed_path(base: &str, n: u64) -> String {
+    match base.rsplit_once('.') {
+        Some((stem, ext)) => format!("{stem}-{n}.{ext}"),
+        None => format!("{base}-{n}"),
+    }
+}
+
 // --- Exécution ---
 
 fn main() {
     let args = Args::parse();
 
     // Vérification des arguments
-    if args.prefix.is_none() && args.suffix.is_none() {
-        eprintln!("Error: You must specify at least one of --prefix or --suffix");
+    if args.starts_with.is_empty() && args.ends_with.is_empty() {
+        eprintln!("Error: You must specify at least one of --starts-with or --ends-with");
         eprintln!();
         eprintln!("Examples:");
-        eprintln!("  kas-vanity --prefix test");
-        eprintln!("  kas-vanity --suffix 2025");
-        eprintln!("  kas-vanity --prefix test --suffix 2025");
+        eprintln!("  kas-vanity --starts-with test:1");
+        eprintln!("  kas-vanity --ends-with 2025:1");
+        eprintln!("  kas-vanity --starts-with abc:2 --ends-with xyz:1");
         std::process::exit(1);
     }
 
@@ -89,57 +343,115 @@ fn main() {
             .expect("Failed to build thread pool");
     }
 
+    // Refuse a pre-existing outfile up front; every match found this run is
+    // then appended to it, so this check only ever needs to run once. The
+    // handle itself is also opened just once here and shared (behind a
+    // mutex) by every grind thread, rather than reopened per match.
+    let outfile = if !args.no_outfile {
+        check_for_overwrite(&args.outfile, args.force);
+        Some(Outfile::open(&args.outfile))
+    } else {
+        None
+    };
+
+    let passphrase = acquire_passphrase(args.passphrase);
+    let language: bip39::Language = args.language.into();
+    let network: kaspa_addresses::Prefix = args.network.into();
+
+    let derivation_spec = match &args.path {
+        Some(path) => match bip32::DerivationPath::from_str(path) {
+            Ok(path) => crate::vanity::DerivationSpec::Custom(path),
+            Err(e) => {
+                eprintln!("Error: Invalid --path '{path}': {e}");
+                std::process::exit(1);
+            }
+        },
+        None => crate::vanity::DerivationSpec::AccountChange { account: args.account, change: args.change },
+    };
+    // `--accounts` conflicts with `--path` at the clap level, so this only
+    // ever runs for the account/change form.
+    let accounts = args.accounts.max(1);
+
     // --- Validation ---
 
-    // Bech32 charset excludes '1', 'b', 'i', 'o'.
-    // Attention: These characters are strictly forbidden.
-    const INVALID_CHARS: &[char] = &['1', 'b', 'i', 'o'];
-    
-    let prefix = args.prefix.map(|p| {
-        let normalized = if args.case_sensitive { p } else { p.to_lowercase() };
-        validate_pattern(&normalized, "prefix", INVALID_CHARS);
-        normalized
-    });
+    // Every scanned account offset (`--account` plus up to `--accounts - 1`)
+    // must still fit below the hardened-index boundary, or derive_batch
+    // silently skips it and the run just spams the generic derivation
+    // warning forever instead of failing fast.
+    if let Some(highest_account) = args.account.checked_add(accounts.saturating_sub(1)) {
+        if highest_account >= bip32::ChildNumber::HARDENED_FLAG {
+            eprintln!(
+                "Error: --account {} (scanning up to account {highest_account} with --accounts {accounts}) exceeds the hardened index limit of {}",
+                args.account,
+                bip32::ChildNumber::HARDENED_FLAG - 1
+            );
+            std::process::exit(1);
+        }
+    } else {
+        eprintln!("Error: --account {} with --accounts {accounts} overflows u32", args.account);
+        std::process::exit(1);
+    }
 
-    let suffix = args.suffix.map(|s| {
-        let normalized = if args.case_sensitive { s } else { s.to_lowercase() };
-        validate_pattern(&normalized, "suffix", INVALID_CHARS);
-        normalized
-    });
+    // Chaque cible est validée indépendamment (charset Bech32).
+    let targets: Vec<GrindMatch> = args
+        .starts_with
+        .iter()
+        .map(|spec| GrindMatch::parse(spec, true, args.case_sensitive, "starts-with"))
+        .chain(
+            args.ends_with
+                .iter()
+                .map(|spec| GrindMatch::parse(spec, false, args.case_sensitive, "ends-with")),
+        )
+        .collect();
+    let targets = Arc::new(targets);
 
     // --- Initialisation ---
 
-    // Calculate target probability
-    let prefix_len = prefix.as_ref().map_or(0, |p| p.len());
-    let suffix_len = suffix.as_ref().map_or(0, |s| s.len());
-    let total_len = prefix_len + suffix_len;
-    let prob_single = 1.0 / 32.0f64.powi(total_len as i32);
+    // Calculate target probability, using the shortest pattern as a rough
+    // worst-case estimate of how quickly the first match should appear.
+    let shortest_len = targets
+        .iter()
+        .map(|t| t.starts.len() + t.ends.len())
+        .min()
+        .unwrap_or(0);
+    let prob_single = 1.0 / 32.0f64.powi(shortest_len as i32);
 
-    println!("Searching for prefix: {:?}", prefix.as_deref().unwrap_or(""));
-    if let Some(s) = &suffix {
-        println!("Searching for suffix: {s}");
+    for target in targets.iter() {
+        let remaining = target.count.load(Ordering::Relaxed);
+        if !target.starts.is_empty() {
+            println!("Searching for prefix: {:?} (x{remaining})", target.starts);
+        }
+        if !target.ends.is_empty() {
+            println!("Searching for suffix: {:?} (x{remaining})", target.ends);
+        }
     }
     println!("Difficulty: 1 in {:.0} (approx)", 1.0 / prob_single);
-    println!("Scan limit: {} addresses per mnemonic", args.scan_limit);
+    println!(
+        "Scan limit: {} addresses per mnemonic ({} accounts x {} indices)",
+        accounts as u64 * args.scan_limit as u64, accounts, args.scan_limit
+    );
     println!("Using {} threads...", rayon::current_num_threads());
 
     let start_time = std::time::Instant::now();
-    let found = Arc::new(AtomicBool::new(false));
     let counter = Arc::new(AtomicU64::new(0));
+    // Counts paper wallets written so far, so a multi-match run (chunk0-1's
+    // whole point) doesn't have each match silently overwrite the last one's
+    // PDF at the same fixed `--paper-wallet` path.
+    let paper_wallet_seq = Arc::new(AtomicU64::new(0));
 
     // --- Boucle Principale ---
     // Parallel infinite loop searching for matching addresses.
     // La chasse commence.
     rayon::iter::repeat(()).for_each(|_| {
-        if found.load(Ordering::Relaxed) {
+        if targets.iter().all(|t| t.count.load(Ordering::Relaxed) == 0) {
             return;
         }
 
-        let mnemonic = generate_random_mnemonic(args.words);
-        
-        // Derive batch of addresses (0..scan_limit)
-        let addresses = crate::vanity::derive_batch(&mnemonic, args.scan_limit);
-        
+        let mnemonic = generate_random_mnemonic(args.words, language);
+
+        // Derive batch of addresses across all scanned accounts and indices.
+        let addresses = crate::vanity::derive_batch(&mnemonic, &passphrase, network, &derivation_spec, accounts, args.scan_limit);
+
         if addresses.is_empty() {
              eprintln!("Warning: Failed to derive addresses (should be rare)");
              return;
@@ -153,10 +465,12 @@ fn main() {
             println!("Checked {count} addresses... ({:.2}% chance)", prob * 100.0);
         }
 
-        for (index, address) in addresses {
+        for (account_offset, index, address) in addresses {
             let addr_str = address.to_string();
-            let payload = addr_str.split(':').nth(1).unwrap_or("");
-            
+            // `splitn(2, ..)` so the split works the same for every network
+            // prefix ("kaspa", "kaspatest", "kaspasim", "kaspadev").
+            let payload = addr_str.splitn(2, ':').nth(1).unwrap_or("");
+
             // Kaspa address format:
             // 1. Version prefix ('q')
             // 2. Limited char (p, q, r, z)
@@ -164,39 +478,54 @@ fn main() {
             //
             // NB: We skip the first 2 characters for prefix matching.
             let searchable = if payload.len() > 2 { &payload[2..] } else { "" };
+            // Normalized once per address rather than once per target below,
+            // since there can be many grind targets checked per candidate.
+            let searchable_normalized =
+                if args.case_sensitive { searchable.to_string() } else { searchable.to_lowercase() };
 
-            // Vérification du préfixe
-            let prefix_match = prefix.as_ref().is_none_or(|p| {
-                if args.case_sensitive {
-                    searchable.starts_with(p)
-                } else {
-                    searchable.to_lowercase().starts_with(p)
+            for target in targets.iter() {
+                if !target.matches(&searchable_normalized) {
+                    continue;
                 }
-            });
-
-            // Vérification du suffixe
-            let suffix_match = suffix.as_ref().is_none_or(|s| {
-                if args.case_sensitive {
-                    searchable.ends_with(s)
-                } else {
-                    searchable.to_lowercase().ends_with(s)
+                if !target.claim() {
+                    continue;
                 }
-            });
 
-            if prefix_match && suffix_match {
                 let elapsed = start_time.elapsed();
-                println!("\n[MATCH FOUND]");
+                let full_path = derivation_spec.describe(account_offset, index);
+                println!("\n[MATCH FOUND] starts={:?} ends={:?}", target.starts, target.ends);
                 println!("Address: {addr_str}");
                 println!("Mnemonic: {mnemonic}");
-                if args.scan_limit > 1 {
-                    println!("Path Index: {index} (m/44'/111111'/0'/0/{index})");
+                if accounts > 1 || args.scan_limit > 1 {
+                    println!("Derivation Path: {full_path}");
                 }
                 println!("Time taken: {elapsed:.2?}");
-                
-                // Signal victory to all threads
-                found.store(true, Ordering::Relaxed);
-                std::process::exit(0);
+
+                if let Some(outfile) = &outfile {
+                    let export = WalletExport {
+                        mnemonic: mnemonic.to_string(),
+                        address: addr_str.clone(),
+                        derivation_path: full_path,
+                    };
+                    outfile.write_result(&args.outfile, &export);
+                }
+
+                if let Some(path) = &args.paper_wallet {
+                    let seq = paper_wallet_seq.fetch_add(1, Ordering::Relaxed);
+                    let path = if seq == 0 { path.clone() } else { indexed_path(path, seq) };
+                    if let Err(e) = crate::paper_wallet::generate(&path, &addr_str, &mnemonic.to_string()) {
+                        eprintln!("Warning: Failed to generate paper wallet '{path}': {e}");
+                    } else {
+                        println!("Wrote paper wallet to '{path}'");
+                    }
+                }
             }
         }
+
+        // All targets satisfied: stop the hunt.
+        if targets.iter().all(|t| t.count.load(Ordering::Relaxed) == 0) {
+            println!("\nAll grind targets satisfied.");
+            std::process::exit(0);
+        }
     });
 }
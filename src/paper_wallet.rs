@@ -0,0 +1,133 @@
+//! Module: Paper Wallet
+//! But: Renders a found vanity result into an offline, printable PDF —
+//!      QR codes plus human-readable text for the address and mnemonic.
+//!
+//! *Signed: kryon.kas*
+
+use printpdf::{ColorBits, ColorSpace, Image, ImageTransform, ImageXObject, Mm, PdfDocument, Px};
+use qrcode::{Color as QrColor, QrCode};
+
+// --- Mise en page ---
+
+const PAGE_WIDTH_MM: f32 = 210.0; // A4
+const PAGE_HEIGHT_MM: f32 = 297.0;
+const QR_SIZE_MM: f32 = 80.0;
+
+/// Pixels per QR module. `qrcode`'s own `image`-crate renderer pulls in
+/// `image` 0.25, while `printpdf`'s `embedded_images` feature is pinned to
+/// `image` 0.24 internally — two incompatible versions of the same crate
+/// that can't hand a buffer to each other. Rasterizing the module grid by
+/// hand (via `QrCode::to_colors`) and building the `ImageXObject` directly
+/// sidesteps both, at the cost of upscaling each module ourselves.
+const MODULE_PX: usize = 8;
+
+/// Renders `data` as a QR code and wraps it as a `printpdf` image, returning
+/// it alongside the per-axis scale factor that fits it into a
+/// `QR_SIZE_MM`-square area. The rendered pixel size varies with the QR
+/// version (driven by `data`'s length), so the scale is derived from the
+/// actual buffer dimensions rather than assumed.
+///
+/// `printpdf`'s own measurements (`Mm`, `ImageTransform` scale) are `f32`,
+/// so this stays `f32` throughout rather than converting at every call site.
+fn qr_image(data: &str) -> (Image, f32, f32) {
+    let code = QrCode::new(data.as_bytes()).expect("Failed to encode QR code");
+    let modules = code.width();
+    let colors = code.to_colors();
+    let size_px = modules * MODULE_PX;
+
+    let mut image_data = vec![0xffu8; size_px * size_px];
+    for (i, color) in colors.iter().enumerate() {
+        if *color == QrColor::Light {
+            continue;
+        }
+        let module_x = i % modules;
+        let module_y = i / modules;
+        for dy in 0..MODULE_PX {
+            let row_start = (module_y * MODULE_PX + dy) * size_px + module_x * MODULE_PX;
+            image_data[row_start..row_start + MODULE_PX].fill(0x00);
+        }
+    }
+
+    let image = Image::from(ImageXObject {
+        width: Px(size_px),
+        height: Px(size_px),
+        color_space: ColorSpace::Greyscale,
+        bits_per_component: ColorBits::Bit8,
+        interpolate: false,
+        image_data,
+        image_filter: None,
+        smask: None,
+        clipping_bbox: None,
+    });
+
+    // printpdf places an image with no `dpi` set at 300 dpi, so its native
+    // size is `size_px / 300` inches; scale that to `QR_SIZE_MM`.
+    let native_mm = size_px as f32 / 300.0 * 25.4;
+    let scale = QR_SIZE_MM / native_mm;
+    (image, scale, scale)
+}
+
+/// Generates a two-sided paper wallet PDF at `path`: the address and its QR
+/// code on page one, the mnemonic and its QR code on page two.
+///
+/// The two are kept on separate pages deliberately — the address is public
+/// and can be shared or scanned freely, while the mnemonic page is the
+/// secret half and should stay offline, so splitting them makes it obvious
+/// which page is safe to show someone.
+pub fn generate(path: &str, address: &str, mnemonic: &str) -> Result<(), String> {
+    let (doc, address_page, address_layer) =
+        PdfDocument::new("Kaspa Vanity Paper Wallet", Mm(PAGE_WIDTH_MM), Mm(PAGE_HEIGHT_MM), "Address");
+    let address_layer = doc.get_page(address_page).get_layer(address_layer);
+
+    let qr_x = (PAGE_WIDTH_MM - QR_SIZE_MM) / 2.0;
+    let qr_y = PAGE_HEIGHT_MM - QR_SIZE_MM - 40.0;
+
+    let (address_qr, address_scale_x, address_scale_y) = qr_image(address);
+    address_qr.add_to_layer(
+        address_layer.clone(),
+        ImageTransform {
+            translate_x: Some(Mm(qr_x)),
+            translate_y: Some(Mm(qr_y)),
+            scale_x: Some(address_scale_x),
+            scale_y: Some(address_scale_y),
+            ..Default::default()
+        },
+    );
+
+    let font = doc
+        .add_builtin_font(printpdf::BuiltinFont::Courier)
+        .map_err(|e| format!("Failed to load font: {e}"))?;
+    address_layer.use_text(address, 12.0, Mm(15.0), Mm(qr_y - 15.0), &font);
+    address_layer.use_text("KASPA ADDRESS (public, safe to share)", 10.0, Mm(15.0), Mm(PAGE_HEIGHT_MM - 20.0), &font);
+
+    let (mnemonic_page, mnemonic_layer) = doc.add_page(Mm(PAGE_WIDTH_MM), Mm(PAGE_HEIGHT_MM), "Mnemonic");
+    let mnemonic_layer = doc.get_page(mnemonic_page).get_layer(mnemonic_layer);
+
+    let (mnemonic_qr, mnemonic_scale_x, mnemonic_scale_y) = qr_image(mnemonic);
+    mnemonic_qr.add_to_layer(
+        mnemonic_layer.clone(),
+        ImageTransform {
+            translate_x: Some(Mm(qr_x)),
+            translate_y: Some(Mm(qr_y)),
+            scale_x: Some(mnemonic_scale_x),
+            scale_y: Some(mnemonic_scale_y),
+            ..Default::default()
+        },
+    );
+
+    mnemonic_layer.use_text(
+        "SEED PHRASE (secret — never share, never type into a device connected to the internet)",
+        10.0,
+        Mm(15.0),
+        Mm(PAGE_HEIGHT_MM - 20.0),
+        &font,
+    );
+    for (i, line) in mnemonic.split_whitespace().collect::<Vec<_>>().chunks(4).enumerate() {
+        let y = qr_y - 15.0 - (i as f32 * 6.0);
+        mnemonic_layer.use_text(line.join(" "), 11.0, Mm(15.0), Mm(y), &font);
+    }
+
+    let file = std::fs::File::create(path).map_err(|e| format!("Failed to create '{path}': {e}"))?;
+    doc.save(&mut std::io::BufWriter::new(file))
+        .map_err(|e| format!("Failed to save PDF '{path}': {e}"))
+}
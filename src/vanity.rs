@@ -3,17 +3,17 @@
 //!
 //! *Signed: kryon.kas*
 
-use bip39::Mnemonic;
+use bip39::{Language, Mnemonic};
 use bip32::XPrv;
 use kaspa_addresses::{Address, Prefix};
 use rand::RngCore;
 
 // --- Génération ---
 
-/// Generates a random BIP-39 mnemonic.
-pub fn generate_random_mnemonic(word_count: usize) -> Mnemonic {
+/// Generates a random BIP-39 mnemonic in the given wordlist language.
+pub fn generate_random_mnemonic(word_count: usize, language: Language) -> Mnemonic {
     let mut rng = rand::rng();
-    
+
     // Entropy selection:
     // 12 words = 128 bits
     // 24 words = 256 bits
@@ -22,65 +22,132 @@ pub fn generate_random_mnemonic(word_count: usize) -> Mnemonic {
         24 => 32,
         _ => 32, // Défaut: 24 words
     };
-    
+
     let mut entropy = vec![0u8; entropy_len];
     rng.fill_bytes(&mut entropy);
 
-    Mnemonic::from_entropy(&entropy).expect("Failed to generate mnemonic")
+    Mnemonic::from_entropy_in(language, &entropy).expect("Failed to generate mnemonic")
 }
 
 // --- Dérivation ---
 
-/// Derives a batch of Kaspa addresses from a mnemonic.
-/// Optimizes performance by deriving the account key once and iterating indices.
-pub fn derive_batch(mnemonic: &Mnemonic, limit: u32) -> Vec<(u32, Address)> {
-    // Seed generation (no passphrase)
-    let seed = mnemonic.to_seed("");
+/// Which account/chain derivation path to use below the master key.
+#[derive(Clone, Debug)]
+pub enum DerivationSpec {
+    /// BIP-44-style `m/44'/111111'/{account}'/{change}`. When scanning
+    /// multiple accounts, `account` is the first of a contiguous range.
+    AccountChange { account: u32, change: u32 },
+    /// Escape hatch: a fully custom path below the master key, up to but
+    /// not including the address index. Mutually exclusive with multi-account
+    /// scanning (the given path is used as-is).
+    Custom(bip32::DerivationPath),
+}
 
-    // Master extended private key
-    let Ok(xprv) = XPrv::new(seed) else { return vec![] };
+impl DerivationSpec {
+    /// Builds the account/chain path for account offset `n` (ignored for `Custom`).
+    fn path_for_account(&self, n: u32) -> Result<bip32::DerivationPath, bip32::Error> {
+        match self {
+            DerivationSpec::AccountChange { account, change } => {
+                bip32::DerivationPath::from_str(&format!("m/44'/111111'/{}'/{change}", account + n))
+            }
+            DerivationSpec::Custom(path) => Ok(path.clone()),
+        }
+    }
 
-    // Kaspa Account Derivation Path: m/44'/111111'/0'/0
-    let path = "m/44'/111111'/0'/0";
-    let Ok(derivation_path) = bip32::DerivationPath::from_str(path) else { return vec![] };
-
-    // Derive the account/chain extended private key
-    let mut account_xprv = xprv;
-    for child in derivation_path {
-        if let Ok(child_key) = account_xprv.derive_child(child) {
-            account_xprv = child_key;
-        } else {
-            return vec![];
+    /// Renders the full derivation path (account/chain plus address index)
+    /// for display and for the wallet export.
+    pub fn describe(&self, account_offset: u32, index: u32) -> String {
+        match self {
+            DerivationSpec::AccountChange { account, change } => {
+                format!("m/44'/111111'/{}'/{change}/{index}", account + account_offset)
+            }
+            DerivationSpec::Custom(path) => format!("{path}/{index}"),
         }
     }
+}
+
+/// Derives a batch of Kaspa addresses from a mnemonic, scanning `accounts`
+/// consecutive accounts and `scan_limit` address indices within each.
+///
+/// Optimizes performance by deriving the (expensive) master key once per
+/// mnemonic and reusing it across every account and index, so a single
+/// generated seed yields `accounts * scan_limit` candidate addresses.
+///
+/// `passphrase` is the optional BIP-39 "25th word"; it must match whatever a
+/// wallet restoring this mnemonic is given, or the derived addresses diverge.
+///
+/// `prefix` selects the network (mainnet/testnet/simnet/devnet) the derived
+/// addresses are encoded for.
+pub fn derive_batch(
+    mnemonic: &Mnemonic,
+    passphrase: &str,
+    prefix: Prefix,
+    spec: &DerivationSpec,
+    accounts: u32,
+    scan_limit: u32,
+) -> Vec<(u32, u32, Address)> {
+    let seed = mnemonic.to_seed(passphrase);
+
+    // Master extended private key (the expensive part, derived once).
+    let Ok(xprv) = XPrv::new(seed) else { return vec![] };
+
+    let mut results = Vec::with_capacity((accounts.max(1) as usize) * scan_limit as usize);
+
+    for account_offset in 0..accounts.max(1) {
+        let Ok(derivation_path) = spec.path_for_account(account_offset) else { continue };
+
+        // Derive the account/chain extended private key from the master key.
+        let mut account_xprv = xprv.clone();
+        let mut ok = true;
+        for child in derivation_path {
+            match account_xprv.derive_child(child) {
+                Ok(child_key) => account_xprv = child_key,
+                Err(_) => {
+                    ok = false;
+                    break;
+                }
+            }
+        }
+        if !ok {
+            continue;
+        }
 
-    let mut results = Vec::with_capacity(limit as usize);
+        for index in 0..scan_limit {
+            // Derive final child (Address Index)
+            if let Ok(child_xprv) = account_xprv.derive_child(bip32::ChildNumber::new(index, false).unwrap()) {
+                // Public Key Extraction
+                let extended_pubkey = child_xprv.public_key();
+                let public_key = extended_pubkey.public_key();
 
-    for index in 0..limit {
-        // Derive final child (Address Index)
-        if let Ok(child_xprv) = account_xprv.derive_child(bip32::ChildNumber::new(index, false).unwrap()) {
-            // Public Key Extraction
-            let extended_pubkey = child_xprv.public_key();
-            let public_key = extended_pubkey.public_key();
+                // Compression
+                let compressed_pubkey = public_key.to_encoded_point(true);
+                let compressed_bytes = compressed_pubkey.as_bytes();
 
-            // Compression
-            let compressed_pubkey = public_key.to_encoded_point(true);
-            let compressed_bytes = compressed_pubkey.as_bytes();
+                // X-Only Public Key (Schnorr)
+                let x_only_pubkey = &compressed_bytes[1..];
 
-            // X-Only Public Key (Schnorr)
-            let x_only_pubkey = &compressed_bytes[1..];
+                // Address Creation
+                let address = Address::new(prefix, kaspa_addresses::Version::PubKey, x_only_pubkey);
 
-            // Address Creation
-            let address = Address::new(Prefix::Mainnet, kaspa_addresses::Version::PubKey, x_only_pubkey);
-            
-            results.push((index, address));
+                results.push((account_offset, index, address));
+            }
         }
     }
 
     results
 }
 
+// --- Validation ---
 
+/// Bech32 excludes these characters to avoid visual confusion: '1' is the
+/// separator, 'b'/'i'/'o' are easily mistaken for '6'/'1'/'l'/'0'.
+pub const INVALID_BECH32_CHARS: &[char] = &['1', 'b', 'i', 'o'];
+
+/// Returns the first character in `pattern` that is outside the Bech32
+/// charset, if any.
+pub fn first_invalid_bech32_char(pattern: &str) -> Option<char> {
+    pattern.chars().find(|c| INVALID_BECH32_CHARS.contains(c))
+}
 
 // --- Helpers ---
 
@@ -92,22 +159,147 @@ mod tests {
 
     #[test]
     fn test_mnemonic_generation() {
-        let mnemonic = generate_random_mnemonic(12);
+        let mnemonic = generate_random_mnemonic(12, Language::English);
         assert_eq!(mnemonic.word_count(), 12);
-        
-        let mnemonic = generate_random_mnemonic(24);
+
+        let mnemonic = generate_random_mnemonic(24, Language::English);
         assert_eq!(mnemonic.word_count(), 24);
     }
 
     #[test]
     fn test_derive_batch() {
-        let mnemonic = generate_random_mnemonic(12);
-        let results = derive_batch(&mnemonic, 10);
-        
+        let mnemonic = generate_random_mnemonic(12, Language::English);
+        let spec = DerivationSpec::AccountChange { account: 0, change: 0 };
+        let results = derive_batch(&mnemonic, "", Prefix::Mainnet, &spec, 1, 10);
+
         assert_eq!(results.len(), 10);
-        for (i, (index, address)) in results.iter().enumerate() {
+        for (i, (account, index, address)) in results.iter().enumerate() {
+            assert_eq!(*account, 0);
             assert_eq!(*index, i as u32);
             assert!(address.to_string().starts_with("kaspa:"));
         }
     }
+
+    #[test]
+    fn test_derive_batch_multi_account() {
+        let mnemonic = generate_random_mnemonic(12, Language::English);
+        let spec = DerivationSpec::AccountChange { account: 0, change: 0 };
+        let results = derive_batch(&mnemonic, "", Prefix::Mainnet, &spec, 3, 2);
+
+        assert_eq!(results.len(), 6);
+        let accounts: Vec<u32> = results.iter().map(|(a, _, _)| *a).collect();
+        assert_eq!(accounts, vec![0, 0, 1, 1, 2, 2]);
+    }
+
+    #[test]
+    fn test_derive_batch_custom_path_matches_equivalent_account_change() {
+        let mnemonic = generate_random_mnemonic(12, Language::English);
+
+        let custom = DerivationSpec::Custom(bip32::DerivationPath::from_str("m/44'/111111'/0'/0").unwrap());
+        let account_change = DerivationSpec::AccountChange { account: 0, change: 0 };
+
+        let custom_results = derive_batch(&mnemonic, "", Prefix::Mainnet, &custom, 1, 3);
+        let account_change_results = derive_batch(&mnemonic, "", Prefix::Mainnet, &account_change, 1, 3);
+
+        assert_eq!(
+            custom_results.iter().map(|(_, _, a)| a.to_string()).collect::<Vec<_>>(),
+            account_change_results.iter().map(|(_, _, a)| a.to_string()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_derivation_spec_describe() {
+        let custom = DerivationSpec::Custom(bip32::DerivationPath::from_str("m/44'/111111'/0'/0").unwrap());
+        assert_eq!(custom.describe(0, 5), "m/44'/111111'/0'/0/5");
+
+        let account_change = DerivationSpec::AccountChange { account: 2, change: 1 };
+        assert_eq!(account_change.describe(3, 5), "m/44'/111111'/5'/1/5");
+    }
+}
+
+// --- Propriétés ---
+
+#[cfg(test)]
+mod proptests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Bech32 charset minus the four excluded characters.
+    const BECH32_CHARSET: &str = "qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+    fn entropy_bytes() -> impl Strategy<Value = Vec<u8>> {
+        prop_oneof![
+            prop::collection::vec(any::<u8>(), 16),
+            prop::collection::vec(any::<u8>(), 32),
+        ]
+    }
+
+    fn bech32_string() -> impl Strategy<Value = String> {
+        let chars: Vec<char> = BECH32_CHARSET.chars().collect();
+        prop::collection::vec(prop::sample::select(chars), 0..12)
+            .prop_map(|cs| cs.into_iter().collect())
+    }
+
+    proptest! {
+        /// `derive_batch` always returns exactly `limit` entries, with
+        /// indices running `0..limit` in order.
+        #[test]
+        fn derive_batch_returns_limit_entries_in_order(entropy in entropy_bytes(), limit in 1u32..20) {
+            let mnemonic = Mnemonic::from_entropy_in(Language::English, &entropy).unwrap();
+            let spec = DerivationSpec::AccountChange { account: 0, change: 0 };
+            let results = derive_batch(&mnemonic, "", Prefix::Mainnet, &spec, 1, limit);
+
+            prop_assert_eq!(results.len(), limit as usize);
+            for (i, (_, index, _)) in results.iter().enumerate() {
+                prop_assert_eq!(*index, i as u32);
+            }
+        }
+
+        /// Every derived address round-trips through `to_string`/`parse`,
+        /// and its x-only pubkey is always 32 bytes (a valid Schnorr key).
+        #[test]
+        fn derived_addresses_round_trip_and_have_32_byte_pubkeys(entropy in entropy_bytes()) {
+            let mnemonic = Mnemonic::from_entropy_in(Language::English, &entropy).unwrap();
+            let spec = DerivationSpec::AccountChange { account: 0, change: 0 };
+            let results = derive_batch(&mnemonic, "", Prefix::Mainnet, &spec, 1, 1);
+            let (_, _, address) = &results[0];
+
+            prop_assert_eq!(address.payload.len(), 32);
+
+            let parsed: Address = address.to_string().parse().expect("round-trip parse");
+            prop_assert_eq!(parsed.to_string(), address.to_string());
+        }
+
+        /// Two calls with the same mnemonic and index produce identical
+        /// addresses: derivation is deterministic.
+        #[test]
+        fn derivation_is_deterministic(entropy in entropy_bytes()) {
+            let mnemonic = Mnemonic::from_entropy_in(Language::English, &entropy).unwrap();
+            let spec = DerivationSpec::AccountChange { account: 0, change: 0 };
+            let first = derive_batch(&mnemonic, "", Prefix::Mainnet, &spec, 1, 3);
+            let second = derive_batch(&mnemonic, "", Prefix::Mainnet, &spec, 1, 3);
+
+            prop_assert_eq!(
+                first.iter().map(|(_, _, a)| a.to_string()).collect::<Vec<_>>(),
+                second.iter().map(|(_, _, a)| a.to_string()).collect::<Vec<_>>()
+            );
+        }
+
+        /// Any string built purely from the Bech32 charset is accepted.
+        #[test]
+        fn valid_bech32_charset_is_always_accepted(pattern in bech32_string()) {
+            prop_assert_eq!(first_invalid_bech32_char(&pattern), None);
+        }
+
+        /// Any string containing '1', 'b', 'i' or 'o' is rejected.
+        #[test]
+        fn excluded_chars_are_always_rejected(
+            prefix in bech32_string(),
+            bad_char in prop::sample::select(INVALID_BECH32_CHARS.to_vec()),
+            suffix in bech32_string(),
+        ) {
+            let pattern = format!("{prefix}{bad_char}{suffix}");
+            prop_assert!(first_invalid_bech32_char(&pattern).is_some());
+        }
+    }
 }